@@ -0,0 +1,158 @@
+use crate::web::AppState;
+use axum::{
+    extract::FromRequestParts,
+    http::{request::Parts, StatusCode},
+};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const SESSION_COOKIE_NAME: &str = "fbrss_session";
+const SESSION_DURATION_SECS: i64 = 24 * 60 * 60;
+
+/// Proof that a request carried a signed, unexpired session cookie.
+///
+/// Add this as a handler argument to require a logged-in admin; axum runs the
+/// extractor before the handler body, so an invalid or missing cookie never
+/// reaches the route logic.
+pub struct AuthSession {
+    pub username: String,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn sign(secret: &[u8], payload: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    to_hex(&mac.finalize().into_bytes())
+}
+
+/// Compares two hex-encoded HMAC signatures (or other secret-derived hex
+/// strings, e.g. a password hash) in constant time, so a byte-by-byte
+/// mismatch can't be timed to forge a valid session cookie or password.
+pub(crate) fn signatures_match(a: &str, b: &str) -> bool {
+    let a = a.as_bytes();
+    let b = b.as_bytes();
+    a.len() == b.len() && bool::from(a.ct_eq(b))
+}
+
+/// Hashes a plaintext password with the given per-install salt, for storage
+/// in `Config::admin_password_hash` and comparison at login time.
+pub fn hash_password(password: &str, salt: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(password.as_bytes());
+    to_hex(&hasher.finalize())
+}
+
+/// Builds the `Set-Cookie` header value for a freshly authenticated session.
+pub fn issue_session_cookie(secret: &[u8], username: &str) -> String {
+    let expiry = Utc::now().timestamp() + SESSION_DURATION_SECS;
+    let payload = format!("{}:{}", username, expiry);
+    let signature = sign(secret, &payload);
+    format!(
+        "{}={}:{}; Path=/; HttpOnly; SameSite=Strict",
+        SESSION_COOKIE_NAME, payload, signature
+    )
+}
+
+fn cookie_value(cookie_header: &str) -> Option<&str> {
+    let prefix = format!("{}=", SESSION_COOKIE_NAME);
+    cookie_header
+        .split(';')
+        .map(|part| part.trim())
+        .find_map(|part| part.strip_prefix(prefix.as_str()))
+}
+
+impl FromRequestParts<Arc<AppState>> for AuthSession {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let cookie_header = parts
+            .headers
+            .get(axum::http::header::COOKIE)
+            .and_then(|v| v.to_str().ok())
+            .ok_or((StatusCode::UNAUTHORIZED, "Missing session cookie"))?;
+
+        let value = cookie_value(cookie_header)
+            .ok_or((StatusCode::UNAUTHORIZED, "Missing session cookie"))?;
+
+        let mut signed = value.rsplitn(2, ':');
+        let signature = signed
+            .next()
+            .ok_or((StatusCode::UNAUTHORIZED, "Malformed session cookie"))?;
+        let payload = signed
+            .next()
+            .ok_or((StatusCode::UNAUTHORIZED, "Malformed session cookie"))?;
+
+        if !signatures_match(signature, &sign(&state.session_secret, payload)) {
+            return Err((StatusCode::UNAUTHORIZED, "Invalid session signature"));
+        }
+
+        let mut fields = payload.rsplitn(2, ':');
+        let expiry_str = fields
+            .next()
+            .ok_or((StatusCode::UNAUTHORIZED, "Malformed session cookie"))?;
+        let username = fields
+            .next()
+            .ok_or((StatusCode::UNAUTHORIZED, "Malformed session cookie"))?;
+
+        let expiry: i64 = expiry_str
+            .parse()
+            .map_err(|_| (StatusCode::UNAUTHORIZED, "Malformed session cookie"))?;
+        if Utc::now().timestamp() > expiry {
+            return Err((StatusCode::UNAUTHORIZED, "Session expired"));
+        }
+
+        Ok(AuthSession {
+            username: username.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_password_is_deterministic_and_salt_sensitive() {
+        let h1 = hash_password("hunter2", "salt-a");
+        let h2 = hash_password("hunter2", "salt-a");
+        let h3 = hash_password("hunter2", "salt-b");
+        assert_eq!(h1, h2);
+        assert_ne!(h1, h3);
+    }
+
+    #[test]
+    fn test_sign_is_deterministic_and_key_sensitive() {
+        let s1 = sign(b"secret-a", "alice:123");
+        let s2 = sign(b"secret-a", "alice:123");
+        let s3 = sign(b"secret-b", "alice:123");
+        assert_eq!(s1, s2);
+        assert_ne!(s1, s3);
+    }
+
+    #[test]
+    fn test_signatures_match_is_constant_time_equality() {
+        let sig = sign(b"secret-a", "alice:123");
+        assert!(signatures_match(&sig, &sig));
+        assert!(!signatures_match(&sig, &sign(b"secret-b", "alice:123")));
+        assert!(!signatures_match(&sig, &sig[..sig.len() - 1]));
+    }
+
+    #[test]
+    fn test_cookie_value_extraction() {
+        let header = "other=1; fbrss_session=alice:123:abc; another=2";
+        assert_eq!(cookie_value(header), Some("alice:123:abc"));
+        assert_eq!(cookie_value("other=1"), None);
+    }
+}
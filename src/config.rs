@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::str::FromStr;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
@@ -13,6 +14,73 @@ pub struct Config {
     pub log_filename: String,
     pub database_name: String,
     pub url_filters: HashMap<String, HashMap<String, Vec<String>>>,
+    /// Username required to log in at `POST /api/login`.
+    #[serde(default)]
+    pub admin_username: String,
+    /// Per-install salt mixed into `admin_password_hash`; see `auth::hash_password`.
+    #[serde(default)]
+    pub admin_password_salt: String,
+    /// `sha256(admin_password_salt + password)`, hex-encoded. Never the plaintext password.
+    #[serde(default)]
+    pub admin_password_hash: String,
+    /// Max infinite-scroll iterations per page load. 0 uses `ScrollConfig::default()`.
+    #[serde(default)]
+    pub max_scrolls: u32,
+    /// Minimum randomized delay (ms) between scroll iterations.
+    #[serde(default)]
+    pub scroll_delay_min_ms: u64,
+    /// Maximum randomized delay (ms) between scroll iterations.
+    #[serde(default)]
+    pub scroll_delay_max_ms: u64,
+    /// Firefox profile preferences (e.g. `network.proxy.*`) merged over the
+    /// scraper's defaults; see `scraper::default_firefox_preferences`.
+    #[serde(default)]
+    pub firefox_preferences: HashMap<String, serde_json::Value>,
+    /// Default 6-field cron expression controlling how often every URL is
+    /// rescanned. Overridden per-URL by `url_schedules`. Falls back to
+    /// `refresh_interval_minutes` when unset.
+    #[serde(default)]
+    pub schedule: Option<String>,
+    /// Per-URL cron expression overrides, keyed by the same URL used in
+    /// `url_filters`. Takes precedence over `schedule`.
+    #[serde(default)]
+    pub url_schedules: HashMap<String, String>,
+    /// Maximum number of URLs scraped concurrently. 0 defaults to 3.
+    #[serde(default)]
+    pub max_concurrent_scrapers: u32,
+    /// Minimum delay enforced between two fetches to the same host. 0
+    /// defaults to 5 seconds.
+    #[serde(default)]
+    pub politeness_delay_seconds: u64,
+    /// Optional image cache so RSS enclosures point at a stable local/S3 URL
+    /// instead of Facebook's ephemeral CDN links; see `media::MediaStore`.
+    /// `None` falls back to hot-linking the original CDN URL.
+    #[serde(default)]
+    pub media_storage: Option<MediaStorageConfig>,
+    /// Try a direct HTTP fetch before falling back to WebDriver; see
+    /// `scraper::Scraper::new_http_first`. Lets the crate run without a
+    /// browser stack when the raw HTML is sufficient.
+    #[serde(default)]
+    pub http_first: bool,
+}
+
+/// Where cached listing thumbnails are written and how clients fetch them
+/// back out. Selects a `media::MediaStore` implementation at startup.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MediaStorageConfig {
+    pub kind: MediaStorageKind,
+    /// Local directory (`Filesystem`) or bucket name (`S3`).
+    pub location: String,
+    /// Base URL clients use to fetch cached images back, e.g.
+    /// `https://host/media`.
+    pub public_base_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaStorageKind {
+    Filesystem,
+    S3,
 }
 
 impl Config {
@@ -35,6 +103,47 @@ impl Config {
         if self.refresh_interval_minutes == 0 {
             return Err(anyhow::anyhow!("Refresh interval must be greater than 0"));
         }
+        if self.admin_username.is_empty() || self.admin_password_hash.is_empty() {
+            return Err(anyhow::anyhow!(
+                "admin_username and admin_password_hash must be set"
+            ));
+        }
+
+        if self.scroll_delay_min_ms > self.scroll_delay_max_ms {
+            return Err(anyhow::anyhow!(
+                "scroll_delay_min_ms ({}) must not be greater than scroll_delay_max_ms ({})",
+                self.scroll_delay_min_ms,
+                self.scroll_delay_max_ms
+            ));
+        }
+
+        if let Some(expr) = &self.schedule {
+            cron::Schedule::from_str(expr)
+                .map_err(|e| anyhow::anyhow!("Invalid schedule cron expression '{}': {}", expr, e))?;
+        }
+        for (url_str, expr) in &self.url_schedules {
+            cron::Schedule::from_str(expr).map_err(|e| {
+                anyhow::anyhow!(
+                    "Invalid schedule cron expression '{}' for URL '{}': {}",
+                    expr,
+                    url_str,
+                    e
+                )
+            })?;
+        }
+
+        if let Some(media_storage) = &self.media_storage {
+            if media_storage.location.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "media_storage.location must be set when media_storage is configured"
+                ));
+            }
+            if media_storage.public_base_url.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "media_storage.public_base_url must be set when media_storage is configured"
+                ));
+            }
+        }
 
         for (url_str, filters) in &self.url_filters {
             let parsed_url = url::Url::parse(url_str)
@@ -110,6 +219,19 @@ mod tests {
             log_filename: "test.log".to_string(),
             database_name: "test.db".to_string(),
             url_filters: HashMap::new(),
+            admin_username: "admin".to_string(),
+            admin_password_salt: "salt".to_string(),
+            admin_password_hash: "hash".to_string(),
+            max_scrolls: 0,
+            scroll_delay_min_ms: 0,
+            scroll_delay_max_ms: 0,
+            firefox_preferences: std::collections::HashMap::new(),
+            schedule: None,
+            url_schedules: std::collections::HashMap::new(),
+            max_concurrent_scrapers: 0,
+            politeness_delay_seconds: 0,
+            media_storage: None,
+            http_first: false,
         };
 
         let tmpfile = NamedTempFile::new().unwrap();
@@ -129,6 +251,19 @@ mod tests {
             log_filename: "test.log".to_string(),
             database_name: "test.db".to_string(),
             url_filters: HashMap::new(),
+            admin_username: "admin".to_string(),
+            admin_password_salt: "salt".to_string(),
+            admin_password_hash: "hash".to_string(),
+            max_scrolls: 0,
+            scroll_delay_min_ms: 0,
+            scroll_delay_max_ms: 0,
+            firefox_preferences: std::collections::HashMap::new(),
+            schedule: None,
+            url_schedules: std::collections::HashMap::new(),
+            max_concurrent_scrapers: 0,
+            politeness_delay_seconds: 0,
+            media_storage: None,
+            http_first: false,
         };
 
         assert!(config.validate().is_ok());
@@ -141,6 +276,10 @@ mod tests {
         assert!(config.validate().is_err());
         config.refresh_interval_minutes = 15;
 
+        config.admin_username = String::new();
+        assert!(config.validate().is_err());
+        config.admin_username = "admin".to_string();
+
         let mut invalid_url_filters = HashMap::new();
         invalid_url_filters.insert("not-a-url".to_string(), HashMap::new());
         config.url_filters = invalid_url_filters;
@@ -166,4 +305,136 @@ mod tests {
         config.url_filters = valid_url_filters_with_valid_levels;
         assert!(config.validate().is_ok());
     }
+
+    #[test]
+    fn test_validate_rejects_bad_cron_expressions() {
+        let mut config = Config {
+            server_ip: "127.0.0.1".to_string(),
+            server_port: 5000,
+            currency: "$".to_string(),
+            refresh_interval_minutes: 15,
+            log_filename: "test.log".to_string(),
+            database_name: "test.db".to_string(),
+            url_filters: HashMap::new(),
+            admin_username: "admin".to_string(),
+            admin_password_salt: "salt".to_string(),
+            admin_password_hash: "hash".to_string(),
+            max_scrolls: 0,
+            scroll_delay_min_ms: 0,
+            scroll_delay_max_ms: 0,
+            firefox_preferences: std::collections::HashMap::new(),
+            schedule: None,
+            url_schedules: std::collections::HashMap::new(),
+            max_concurrent_scrapers: 0,
+            politeness_delay_seconds: 0,
+            media_storage: None,
+            http_first: false,
+        };
+
+        config.schedule = Some("0 0 9,12,17 * * Mon-Fri".to_string());
+        assert!(config.validate().is_ok());
+
+        config.schedule = Some("not a cron expression".to_string());
+        assert!(config.validate().is_err());
+        config.schedule = None;
+
+        config
+            .url_schedules
+            .insert("https://example.com".to_string(), "0 0 9 * * *".to_string());
+        assert!(config.validate().is_ok());
+
+        config
+            .url_schedules
+            .insert("https://example.com".to_string(), "nonsense".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_inverted_scroll_delay_bounds() {
+        let mut config = Config {
+            server_ip: "127.0.0.1".to_string(),
+            server_port: 5000,
+            currency: "$".to_string(),
+            refresh_interval_minutes: 15,
+            log_filename: "test.log".to_string(),
+            database_name: "test.db".to_string(),
+            url_filters: HashMap::new(),
+            admin_username: "admin".to_string(),
+            admin_password_salt: "salt".to_string(),
+            admin_password_hash: "hash".to_string(),
+            max_scrolls: 0,
+            scroll_delay_min_ms: 0,
+            scroll_delay_max_ms: 0,
+            firefox_preferences: std::collections::HashMap::new(),
+            schedule: None,
+            url_schedules: std::collections::HashMap::new(),
+            max_concurrent_scrapers: 0,
+            politeness_delay_seconds: 0,
+            media_storage: None,
+            http_first: false,
+        };
+
+        assert!(config.validate().is_ok());
+
+        config.scroll_delay_min_ms = 500;
+        config.scroll_delay_max_ms = 100;
+        assert!(config.validate().is_err());
+
+        config.scroll_delay_min_ms = 100;
+        config.scroll_delay_max_ms = 500;
+        assert!(config.validate().is_ok());
+
+        config.scroll_delay_min_ms = 100;
+        config.scroll_delay_max_ms = 100;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_incomplete_media_storage() {
+        let mut config = Config {
+            server_ip: "127.0.0.1".to_string(),
+            server_port: 5000,
+            currency: "$".to_string(),
+            refresh_interval_minutes: 15,
+            log_filename: "test.log".to_string(),
+            database_name: "test.db".to_string(),
+            url_filters: HashMap::new(),
+            admin_username: "admin".to_string(),
+            admin_password_salt: "salt".to_string(),
+            admin_password_hash: "hash".to_string(),
+            max_scrolls: 0,
+            scroll_delay_min_ms: 0,
+            scroll_delay_max_ms: 0,
+            firefox_preferences: std::collections::HashMap::new(),
+            schedule: None,
+            url_schedules: std::collections::HashMap::new(),
+            max_concurrent_scrapers: 0,
+            politeness_delay_seconds: 0,
+            media_storage: None,
+            http_first: false,
+        };
+
+        assert!(config.validate().is_ok());
+
+        config.media_storage = Some(MediaStorageConfig {
+            kind: MediaStorageKind::Filesystem,
+            location: String::new(),
+            public_base_url: "https://example.com/media".to_string(),
+        });
+        assert!(config.validate().is_err());
+
+        config.media_storage = Some(MediaStorageConfig {
+            kind: MediaStorageKind::Filesystem,
+            location: "/var/media".to_string(),
+            public_base_url: String::new(),
+        });
+        assert!(config.validate().is_err());
+
+        config.media_storage = Some(MediaStorageConfig {
+            kind: MediaStorageKind::Filesystem,
+            location: "/var/media".to_string(),
+            public_base_url: "https://example.com/media".to_string(),
+        });
+        assert!(config.validate().is_ok());
+    }
 }
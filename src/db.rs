@@ -2,6 +2,7 @@ use anyhow::Result;
 use chrono::{DateTime, Duration, Utc};
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::OptionalExtension;
 
 pub struct Database {
     pool: Pool<SqliteConnectionManager>,
@@ -15,6 +16,34 @@ pub struct AdEntry {
     pub url: String,
     pub first_seen: DateTime<Utc>,
     pub last_checked: DateTime<Utc>,
+    /// The last price recorded in `price_history`, when `price` is lower
+    /// than it. `None` if the ad has no history or its latest change was
+    /// not a drop; a rise after a drop clears it even while `price` is still
+    /// below the first-seen price. Only populated by `get_recent_ads`.
+    pub previous_price: Option<String>,
+    /// When `previous_price` was recorded in `price_history` (i.e. when this
+    /// drop actually happened), not when it was last queried. `rss_gen` uses
+    /// this as the item's `pub_date` so it stays stable across repeated
+    /// requests instead of changing on every poll. Only populated alongside
+    /// `previous_price` by `get_recent_ads`.
+    pub previous_price_observed_at: Option<DateTime<Utc>>,
+    /// The listing's thumbnail, if one was found. Points at Facebook's CDN
+    /// unless `media::MediaStore` cached it behind a stable URL.
+    pub image_url: Option<String>,
+}
+
+/// Strips currency symbols/commas and parses the numeric value of a price
+/// string (e.g. `"$1,250"` -> `1250.0`), for comparing two price strings.
+fn parse_price_numeric(price: &str) -> Option<f64> {
+    let cleaned: String = price
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    if cleaned.is_empty() {
+        None
+    } else {
+        cleaned.parse().ok()
+    }
 }
 
 impl Database {
@@ -32,7 +61,19 @@ impl Database {
                 title TEXT,
                 price TEXT,
                 first_seen TEXT,
-                last_checked TEXT
+                last_checked TEXT,
+                image_url TEXT
+            )",
+            [],
+        )?;
+        // Migration for databases created before image_url existed; errors
+        // (e.g. column already present) are expected and ignored.
+        let _ = conn.execute("ALTER TABLE ad_changes ADD COLUMN image_url TEXT", []);
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS price_history (
+                ad_id TEXT,
+                price TEXT,
+                observed_at TEXT
             )",
             [],
         )?;
@@ -44,26 +85,64 @@ impl Database {
             .pool
             .get()
             .map_err(|e| anyhow::anyhow!("Pool connection error: {}", e))?;
-        let mut stmt = conn.prepare("SELECT ad_id FROM ad_changes WHERE ad_id = ?")?;
-        let exists = stmt.exists([&entry.ad_id])?;
+        let mut stmt = conn.prepare("SELECT price FROM ad_changes WHERE ad_id = ?")?;
+        let existing_price: Option<String> = stmt
+            .query_row([&entry.ad_id], |row| row.get(0))
+            .optional()?;
 
         let now_iso = entry.last_checked.to_rfc3339();
 
-        if !exists {
-            conn.execute(
-                "INSERT INTO ad_changes (url, ad_id, title, price, first_seen, last_checked) VALUES (?, ?, ?, ?, ?, ?)",
-                (&entry.url, &entry.ad_id, &entry.title, &entry.price, &entry.first_seen.to_rfc3339(), &now_iso),
-            )?;
-            Ok(true)
-        } else {
-            conn.execute(
-                "UPDATE ad_changes SET last_checked = ?, title = ?, price = ? WHERE ad_id = ?",
-                (&now_iso, &entry.title, &entry.price, &entry.ad_id),
-            )?;
-            Ok(false)
+        match existing_price {
+            None => {
+                conn.execute(
+                    "INSERT INTO ad_changes (url, ad_id, title, price, first_seen, last_checked, image_url) VALUES (?, ?, ?, ?, ?, ?, ?)",
+                    (&entry.url, &entry.ad_id, &entry.title, &entry.price, &entry.first_seen.to_rfc3339(), &now_iso, &entry.image_url),
+                )?;
+                Ok(true)
+            }
+            Some(old_price) => {
+                if old_price != entry.price {
+                    conn.execute(
+                        "INSERT INTO price_history (ad_id, price, observed_at) VALUES (?, ?, ?)",
+                        (&entry.ad_id, &old_price, &now_iso),
+                    )?;
+                }
+                conn.execute(
+                    "UPDATE ad_changes SET last_checked = ?, title = ?, price = ?, image_url = ? WHERE ad_id = ?",
+                    (&now_iso, &entry.title, &entry.price, &entry.image_url, &entry.ad_id),
+                )?;
+                Ok(false)
+            }
         }
     }
 
+    /// Returns this ad's recorded price changes, oldest first. The first
+    /// entry (if any) is the price the ad was originally seen at.
+    pub fn get_price_history(&self, ad_id: &str) -> Result<Vec<(String, DateTime<Utc>)>> {
+        let conn = self
+            .pool
+            .get()
+            .map_err(|e| anyhow::anyhow!("Pool connection error: {}", e))?;
+        let mut stmt = conn.prepare(
+            "SELECT price, observed_at FROM price_history WHERE ad_id = ? ORDER BY observed_at ASC",
+        )?;
+        let rows = stmt
+            .query_map([ad_id], |row| {
+                Ok((row.get::<usize, String>(0)?, row.get::<usize, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(price, observed_at)| {
+                let observed_at = DateTime::parse_from_rfc3339(&observed_at)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now());
+                (price, observed_at)
+            })
+            .collect())
+    }
+
     pub fn prune_old_ads(&self, days_to_keep: i64) -> Result<usize> {
         let conn = self
             .pool
@@ -75,9 +154,28 @@ impl Database {
             "DELETE FROM ad_changes WHERE last_checked < ?",
             [&cutoff_iso],
         )?;
+        conn.execute(
+            "DELETE FROM price_history WHERE observed_at < ?",
+            [&cutoff_iso],
+        )?;
+        conn.execute(
+            "DELETE FROM price_history WHERE ad_id NOT IN (SELECT ad_id FROM ad_changes)",
+            [],
+        )?;
         Ok(deleted)
     }
 
+    /// Total number of rows currently tracked in `ad_changes`, for the
+    /// `/metrics` gauge.
+    pub fn count_ads(&self) -> Result<u64> {
+        let conn = self
+            .pool
+            .get()
+            .map_err(|e| anyhow::anyhow!("Pool connection error: {}", e))?;
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM ad_changes", [], |row| row.get(0))?;
+        Ok(count as u64)
+    }
+
     pub fn get_recent_ads(&self, days: i64) -> Result<Vec<AdEntry>> {
         let conn = self
             .pool
@@ -86,10 +184,10 @@ impl Database {
         let cutoff = Utc::now() - Duration::days(days);
         let cutoff_iso = cutoff.to_rfc3339();
         let mut stmt = conn.prepare(
-            "SELECT ad_id, title, price, url, first_seen, last_checked FROM ad_changes WHERE last_checked >= ? ORDER BY last_checked DESC"
+            "SELECT ad_id, title, price, url, first_seen, last_checked, image_url FROM ad_changes WHERE last_checked >= ? ORDER BY last_checked DESC"
         )?;
 
-        let entries = stmt
+        let mut entries = stmt
             .query_map([&cutoff_iso], |row: &rusqlite::Row| {
                 Ok(AdEntry {
                     ad_id: row.get::<usize, String>(0)?,
@@ -102,10 +200,26 @@ impl Database {
                     last_checked: DateTime::parse_from_rfc3339(&row.get::<usize, String>(5)?)
                         .map(|dt| dt.with_timezone(&Utc))
                         .unwrap_or_else(|_| Utc::now()),
+                    previous_price: None,
+                    previous_price_observed_at: None,
+                    image_url: row.get::<usize, Option<String>>(6)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
+        for entry in &mut entries {
+            let history = self.get_price_history(&entry.ad_id)?;
+            if let Some((last_recorded_price, observed_at)) = history.last() {
+                let is_drop = parse_price_numeric(last_recorded_price)
+                    .zip(parse_price_numeric(&entry.price))
+                    .is_some_and(|(last_recorded, current)| current < last_recorded);
+                if is_drop {
+                    entry.previous_price = Some(last_recorded_price.clone());
+                    entry.previous_price_observed_at = Some(*observed_at);
+                }
+            }
+        }
+
         Ok(entries)
     }
 }
@@ -125,6 +239,9 @@ mod tests {
             url: "https://example.com/ad1".to_string(),
             first_seen: now,
             last_checked: now,
+            previous_price: None,
+            previous_price_observed_at: None,
+            image_url: None,
         };
 
         let is_new = db.insert_or_update_ad(&entry).unwrap();
@@ -145,6 +262,9 @@ mod tests {
             url: "https://example.com/old".to_string(),
             first_seen: old_date,
             last_checked: old_date,
+            previous_price: None,
+            previous_price_observed_at: None,
+            image_url: None,
         };
 
         db.insert_or_update_ad(&entry).unwrap();
@@ -163,6 +283,9 @@ mod tests {
             url: "https://example.com/recent".to_string(),
             first_seen: now,
             last_checked: now,
+            previous_price: None,
+            previous_price_observed_at: None,
+            image_url: None,
         };
 
         db.insert_or_update_ad(&entry).unwrap();
@@ -170,4 +293,159 @@ mod tests {
         assert_eq!(recent.len(), 1);
         assert_eq!(recent[0].ad_id, "recent");
     }
+
+    #[test]
+    fn test_count_ads() {
+        let db = Database::new(":memory:").unwrap();
+        assert_eq!(db.count_ads().unwrap(), 0);
+
+        let now = Utc::now();
+        let entry = AdEntry {
+            ad_id: "counted".to_string(),
+            title: "Counted Ad".to_string(),
+            price: "$50".to_string(),
+            url: "https://example.com/counted".to_string(),
+            first_seen: now,
+            last_checked: now,
+            previous_price: None,
+            previous_price_observed_at: None,
+            image_url: None,
+        };
+        db.insert_or_update_ad(&entry).unwrap();
+        assert_eq!(db.count_ads().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_price_change_is_recorded_in_history() {
+        let db = Database::new(":memory:").unwrap();
+        let now = Utc::now();
+        let mut entry = AdEntry {
+            ad_id: "priced".to_string(),
+            title: "Priced Ad".to_string(),
+            price: "$100".to_string(),
+            url: "https://example.com/priced".to_string(),
+            first_seen: now,
+            last_checked: now,
+            previous_price: None,
+            previous_price_observed_at: None,
+            image_url: None,
+        };
+
+        db.insert_or_update_ad(&entry).unwrap();
+        assert!(db.get_price_history("priced").unwrap().is_empty());
+
+        entry.price = "$80".to_string();
+        entry.last_checked = now;
+        db.insert_or_update_ad(&entry).unwrap();
+
+        let history = db.get_price_history("priced").unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].0, "$100");
+
+        // A second update with the same price shouldn't add another entry.
+        db.insert_or_update_ad(&entry).unwrap();
+        assert_eq!(db.get_price_history("priced").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_get_recent_ads_flags_price_drops() {
+        let db = Database::new(":memory:").unwrap();
+        let now = Utc::now();
+        let mut entry = AdEntry {
+            ad_id: "dropped".to_string(),
+            title: "Dropped Ad".to_string(),
+            price: "$100".to_string(),
+            url: "https://example.com/dropped".to_string(),
+            first_seen: now,
+            last_checked: now,
+            previous_price: None,
+            previous_price_observed_at: None,
+            image_url: None,
+        };
+        db.insert_or_update_ad(&entry).unwrap();
+
+        entry.price = "$75".to_string();
+        db.insert_or_update_ad(&entry).unwrap();
+
+        let recent = db.get_recent_ads(1).unwrap();
+        let dropped = recent.iter().find(|a| a.ad_id == "dropped").unwrap();
+        assert_eq!(dropped.previous_price.as_deref(), Some("$100"));
+        assert!(dropped.previous_price_observed_at.is_some());
+
+        // Re-querying the same unchanged drop must return the same
+        // observed_at, not a fresh timestamp, so `rss_gen` can keep a
+        // stable pub_date across repeated polls.
+        let recent_again = db.get_recent_ads(1).unwrap();
+        let dropped_again = recent_again.iter().find(|a| a.ad_id == "dropped").unwrap();
+        assert_eq!(
+            dropped.previous_price_observed_at,
+            dropped_again.previous_price_observed_at
+        );
+
+        // A price increase shouldn't be flagged as a drop.
+        entry.price = "$90".to_string();
+        db.insert_or_update_ad(&entry).unwrap();
+        let recent = db.get_recent_ads(1).unwrap();
+        let risen = recent.iter().find(|a| a.ad_id == "dropped").unwrap();
+        assert_eq!(risen.previous_price, None);
+    }
+
+    #[test]
+    fn test_prune_old_ads_also_prunes_price_history() {
+        let db = Database::new(":memory:").unwrap();
+        let old_date = Utc::now() - Duration::days(20);
+        let mut entry = AdEntry {
+            ad_id: "stale".to_string(),
+            title: "Stale Ad".to_string(),
+            price: "$100".to_string(),
+            url: "https://example.com/stale".to_string(),
+            first_seen: old_date,
+            last_checked: old_date,
+            previous_price: None,
+            previous_price_observed_at: None,
+            image_url: None,
+        };
+        db.insert_or_update_ad(&entry).unwrap();
+        entry.price = "$90".to_string();
+        entry.last_checked = old_date;
+        db.insert_or_update_ad(&entry).unwrap();
+        assert_eq!(db.get_price_history("stale").unwrap().len(), 1);
+
+        db.prune_old_ads(14).unwrap();
+        assert!(db.get_price_history("stale").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_image_url_round_trips_through_insert_and_update() {
+        let db = Database::new(":memory:").unwrap();
+        let now = Utc::now();
+        let mut entry = AdEntry {
+            ad_id: "with_image".to_string(),
+            title: "Ad With Image".to_string(),
+            price: "$100".to_string(),
+            url: "https://example.com/with_image".to_string(),
+            first_seen: now,
+            last_checked: now,
+            previous_price: None,
+            previous_price_observed_at: None,
+            image_url: Some("https://cdn.example.com/thumb1.jpg".to_string()),
+        };
+        db.insert_or_update_ad(&entry).unwrap();
+
+        let recent = db.get_recent_ads(1).unwrap();
+        let found = recent.iter().find(|a| a.ad_id == "with_image").unwrap();
+        assert_eq!(
+            found.image_url.as_deref(),
+            Some("https://cdn.example.com/thumb1.jpg")
+        );
+
+        entry.image_url = Some("https://cdn.example.com/thumb2.jpg".to_string());
+        db.insert_or_update_ad(&entry).unwrap();
+        let recent = db.get_recent_ads(1).unwrap();
+        let found = recent.iter().find(|a| a.ad_id == "with_image").unwrap();
+        assert_eq!(
+            found.image_url.as_deref(),
+            Some("https://cdn.example.com/thumb2.jpg")
+        );
+    }
 }
@@ -1,145 +1,372 @@
 use crate::config::Config;
 use crate::db::{AdEntry, Database};
 use crate::filter::apply_filters;
-use crate::scraper::{extract_ads, Scraper};
+use crate::media::MediaStore;
+use crate::scraper::{extract_ads, ScrollConfig, Scraper};
 use crate::web::{app, AppState};
 use anyhow::Result;
 use backoff::backoff::Backoff;
 use backoff::ExponentialBackoff;
-use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand};
+use cron::Schedule;
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
 use tokio::time::{sleep, Duration};
 use tracing::{error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+mod auth;
 mod config;
 mod db;
 mod filter;
+mod media;
+mod metrics;
 mod rss_gen;
 mod scraper;
 mod web;
 
-async fn check_for_ads(state: Arc<AppState>) -> Result<()> {
-    let config = state.config.read().await.clone();
-    let urls: Vec<_> = config.url_filters.keys().cloned().collect();
-    if urls.is_empty() {
-        return Ok(());
-    }
+/// Per-host last-fetch timestamps, shared across concurrently scraped URLs so
+/// two tasks never hit the same domain back-to-back.
+type HostThrottle = Arc<Mutex<HashMap<String, std::time::Instant>>>;
+
+/// Blocks until at least `min_delay` has passed since the last fetch to
+/// `host`, then reserves this turn by recording the current time.
+async fn wait_for_host_turn(host_last_fetch: &HostThrottle, host: &str, min_delay: Duration) {
+    loop {
+        let wait = {
+            let mut last_fetch = host_last_fetch.lock().unwrap();
+            let now = std::time::Instant::now();
+            match last_fetch.get(host) {
+                Some(last) if now.duration_since(*last) < min_delay => {
+                    Some(min_delay - now.duration_since(*last))
+                }
+                _ => {
+                    last_fetch.insert(host.to_string(), now);
+                    None
+                }
+            }
+        };
 
-    let num_scrapers = std::cmp::min(3, urls.len());
-    let mut chunks = vec![Vec::new(); num_scrapers];
-    for (i, url) in urls.into_iter().enumerate() {
-        chunks[i % num_scrapers].push(url);
+        match wait {
+            Some(remaining) => sleep(remaining).await,
+            None => return,
+        }
     }
+}
 
-    let mut tasks = Vec::new();
+/// Fetches and extracts ads from a single URL: waits its turn on
+/// `host_last_fetch`, inits a scraper with retry, fetches the page with
+/// retry, then extracts and stores any new ads.
+async fn scrape_one_url(
+    state: Arc<AppState>,
+    config: Config,
+    url: String,
+    host_last_fetch: HostThrottle,
+    politeness_delay: Duration,
+    media_store: Option<Arc<dyn MediaStore>>,
+) {
+    if let Some(host) = url::Url::parse(&url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+    {
+        wait_for_host_turn(&host_last_fetch, &host, politeness_delay).await;
+    }
 
-    for chunk in chunks {
-        let config_clone = config.clone();
-        let state_clone = Arc::clone(&state);
+    info!("Processing URL: {}", url);
+    state.metrics.inc_scrape_attempts();
+
+    let mut scraper = if config.http_first {
+        Scraper::new_http_first().unwrap_or_else(|e| {
+            warn!(
+                "Failed to build HTTP-first scraper for {}, falling back to WebDriver-only: {}",
+                url, e
+            );
+            Scraper::new()
+        })
+    } else {
+        Scraper::new()
+    };
+    if config.max_scrolls > 0 {
+        scraper.set_scroll_config(ScrollConfig {
+            max_scrolls: config.max_scrolls,
+            delay_min_ms: config.scroll_delay_min_ms,
+            delay_max_ms: config.scroll_delay_max_ms,
+        });
+    }
+    if !config.firefox_preferences.is_empty() {
+        scraper.set_firefox_preferences(config.firefox_preferences.clone());
+    }
 
-        let task = tokio::spawn(async move {
-            let mut scraper = Scraper::new();
-            let mut backoff = ExponentialBackoff {
-                max_elapsed_time: Some(Duration::from_secs(60)),
-                ..Default::default()
-            };
+    let mut backoff = ExponentialBackoff {
+        max_elapsed_time: Some(Duration::from_secs(60)),
+        ..Default::default()
+    };
 
-            // Init scraper with retry
-            let mut init_success = false;
-            while let Some(delay) = backoff.next_backoff() {
-                match scraper.init().await {
-                    Ok(_) => {
-                        init_success = true;
-                        break;
-                    }
-                    Err(e) => {
-                        warn!(
-                            "Failed to init scraper, retrying in {:?}... Error: {}",
-                            delay, e
-                        );
-                        sleep(delay).await;
-                    }
-                }
+    // Init scraper with retry
+    let mut init_success = false;
+    while let Some(delay) = backoff.next_backoff() {
+        match scraper.init().await {
+            Ok(_) => {
+                init_success = true;
+                break;
             }
-
-            if !init_success {
-                error!("Failed to initialize scraper after retries");
-                return;
+            Err(e) => {
+                warn!(
+                    "Failed to init scraper for {}, retrying in {:?}... Error: {}",
+                    url, delay, e
+                );
+                sleep(delay).await;
             }
+        }
+    }
 
-            for url in chunk {
-                info!("Processing URL: {}", url);
-
-                let mut fetch_backoff = backoff.clone();
-                fetch_backoff.reset();
-                let mut content = None;
-
-                while let Some(delay) = fetch_backoff.next_backoff() {
-                    match scraper.get_page_content(&url).await {
-                        Ok(c) => {
-                            content = Some(c);
-                            break;
-                        }
-                        Err(e) => {
-                            warn!(
-                                "Failed to fetch content for {}, retrying in {:?}... Error: {}",
-                                url, delay, e
-                            );
-                            sleep(delay).await;
-                        }
-                    }
-                }
+    if !init_success {
+        error!("Failed to initialize scraper for {} after retries", url);
+        state.metrics.inc_scraper_init_failures();
+        return;
+    }
 
-                let Some(content) = content else {
-                    error!("Failed to fetch content for {} after retries", url);
-                    continue;
-                };
-
-                let ads = extract_ads(&content, &config_clone.currency);
-                for (id, title, price, ad_url) in ads {
-                    if apply_filters(&config_clone.url_filters, &url, &title) {
-                        let entry = AdEntry {
-                            ad_id: id,
-                            title,
-                            price,
-                            url: ad_url,
-                            first_seen: chrono::Utc::now(),
-                            last_checked: chrono::Utc::now(),
-                        };
-                        match state_clone.db.insert_or_update_ad(&entry) {
-                            Ok(is_new) => {
-                                if is_new {
-                                    info!("New ad found: {}", entry.title);
-                                }
-                            }
-                            Err(e) => error!("Failed to save ad: {}", e),
-                        }
-                    }
-                }
+    let mut fetch_backoff = backoff.clone();
+    fetch_backoff.reset();
+    let mut content = None;
 
-                let delay = rand::random_range(2..10);
-                sleep(Duration::from_secs(delay)).await;
+    while let Some(delay) = fetch_backoff.next_backoff() {
+        match scraper.get_page_content(&url).await {
+            Ok(c) => {
+                content = Some(c);
+                break;
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to fetch content for {}, retrying in {:?}... Error: {}",
+                    url, delay, e
+                );
+                sleep(delay).await;
             }
+        }
+    }
 
-            let _ = scraper.quit().await;
-        });
+    let _ = scraper.quit().await;
 
-        tasks.push(task);
+    let Some(content) = content else {
+        error!("Failed to fetch content for {} after retries", url);
+        state.metrics.inc_fetch_failures();
+        return;
+    };
+
+    let ads = extract_ads(&content, &config.currency);
+    state.metrics.add_ads_extracted(ads.len() as u64);
+    for (id, title, price, ad_url, image_url) in ads {
+        if apply_filters(&config.url_filters, &url, &title) {
+            let image_url = match (&media_store, &image_url) {
+                (Some(store), Some(source_url)) => match store.store(&id, source_url).await {
+                    Ok(cached_url) => Some(cached_url),
+                    Err(e) => {
+                        warn!("Failed to cache image for ad {}: {}", id, e);
+                        Some(source_url.clone())
+                    }
+                },
+                _ => image_url,
+            };
+            let entry = AdEntry {
+                ad_id: id,
+                title,
+                price,
+                url: ad_url,
+                first_seen: chrono::Utc::now(),
+                last_checked: chrono::Utc::now(),
+                previous_price: None,
+                previous_price_observed_at: None,
+                image_url,
+            };
+            match state.db.insert_or_update_ad(&entry) {
+                Ok(is_new) => {
+                    if is_new {
+                        info!("New ad found: {}", entry.title);
+                        state.metrics.inc_ads_inserted();
+                    }
+                }
+                Err(e) => error!("Failed to save ad: {}", e),
+            }
+        }
     }
+}
 
-    for task in tasks {
-        let _ = task.await;
+async fn check_for_ads(state: Arc<AppState>, urls: Vec<String>) -> Result<()> {
+    let config = state.config.read().await.clone();
+    if urls.is_empty() {
+        return Ok(());
     }
 
+    let cycle_start = std::time::Instant::now();
+
+    let concurrency = if config.max_concurrent_scrapers > 0 {
+        config.max_concurrent_scrapers as usize
+    } else {
+        3
+    };
+    let politeness_delay = Duration::from_secs(if config.politeness_delay_seconds > 0 {
+        config.politeness_delay_seconds
+    } else {
+        5
+    });
+    let host_last_fetch: HostThrottle = Arc::new(Mutex::new(HashMap::new()));
+    let media_store = media::build_media_store(&config.media_storage);
+
+    stream::iter(urls)
+        .map(|url| {
+            let state = Arc::clone(&state);
+            let config = config.clone();
+            let host_last_fetch = Arc::clone(&host_last_fetch);
+            let media_store = media_store.clone();
+            async move {
+                scrape_one_url(
+                    state,
+                    config,
+                    url,
+                    host_last_fetch,
+                    politeness_delay,
+                    media_store,
+                )
+                .await
+            }
+        })
+        .buffer_unordered(concurrency)
+        .for_each(|_| async {})
+        .await;
+
     let _ = state.db.prune_old_ads(14);
+    state.metrics.record_scrape_duration(cycle_start.elapsed());
     Ok(())
 }
 
+/// Splits `config.url_filters` into the URLs that are due to be scraped now
+/// (per `due_at`, tracked across loop iterations) and the delay to sleep
+/// before the next one comes due. URLs without an entry in `due_at` yet are
+/// always considered due immediately.
+fn due_urls_and_next_sleep(
+    config: &Config,
+    due_at: &mut HashMap<String, DateTime<Utc>>,
+) -> (Vec<String>, Duration) {
+    let now = Utc::now();
+    let mut due = Vec::new();
+    let mut soonest: Option<DateTime<Utc>> = None;
+
+    for url in config.url_filters.keys() {
+        let is_due = match due_at.get(url) {
+            Some(next) => *next <= now,
+            None => true,
+        };
+
+        if is_due {
+            due.push(url.clone());
+            let expr = config.url_schedules.get(url).or(config.schedule.as_ref());
+            let next = expr
+                .and_then(|e| Schedule::from_str(e).ok())
+                .and_then(|s| s.upcoming(Utc).next())
+                .unwrap_or_else(|| now + chrono::Duration::minutes(config.refresh_interval_minutes as i64));
+            due_at.insert(url.clone(), next);
+        }
+
+        let next = due_at[url];
+        soonest = Some(soonest.map_or(next, |s| s.min(next)));
+    }
+
+    let sleep_for = match soonest {
+        Some(next) => (next - now)
+            .to_std()
+            .unwrap_or(Duration::from_secs(1)),
+        None => Duration::from_secs(config.refresh_interval_minutes * 60),
+    };
+
+    (due, sleep_for)
+}
+
+#[derive(Parser)]
+#[command(about = "Facebook Marketplace ad watcher and RSS feed server")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the long-lived HTTP server and background scrape loop (default).
+    Serve,
+    /// Run the scrape loop once for every configured URL, then exit.
+    ScrapeOnce,
+    /// Load and validate the config file, then exit.
+    ValidateConfig,
+    /// Delete ad rows (and their price history) last checked more than `--days` ago.
+    Prune {
+        #[arg(long, default_value_t = 14)]
+        days: i64,
+    },
+    /// Print the current RSS feed to stdout.
+    DumpRss,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let command = cli.command.unwrap_or(Command::Serve);
     let config_path = std::env::var("CONFIG_FILE").unwrap_or_else(|_| "config.json".to_string());
 
+    match command {
+        Command::ValidateConfig => {
+            let config = Config::load(&config_path)?;
+            config.validate()?;
+            println!("Config at {} is valid.", config_path);
+            return Ok(());
+        }
+        Command::Prune { days } => {
+            let config = Config::load(&config_path)?;
+            let db = Database::new(&config.database_name)?;
+            let pruned = db.prune_old_ads(days)?;
+            println!("Pruned {} ad(s) last checked more than {} days ago.", pruned, days);
+            return Ok(());
+        }
+        Command::DumpRss => {
+            let config = Config::load(&config_path)?;
+            let db = Database::new(&config.database_name)?;
+            let session_secret: [u8; 32] = rand::random();
+            let state = Arc::new(AppState {
+                config: RwLock::new(config.clone()),
+                db,
+                start_time: std::time::Instant::now(),
+                config_path: config_path.clone(),
+                session_secret: session_secret.to_vec(),
+                metrics: metrics::Metrics::new(),
+            });
+            let ads = state.db.get_recent_ads(7)?;
+            let config = state.config.read().await;
+            let xml = rss_gen::generate_rss(&ads, &config.server_ip, config.server_port)?;
+            println!("{}", xml);
+            return Ok(());
+        }
+        Command::ScrapeOnce => {
+            let config = Config::load(&config_path)?;
+            let db = Database::new(&config.database_name)?;
+            let urls: Vec<String> = config.url_filters.keys().cloned().collect();
+            let session_secret: [u8; 32] = rand::random();
+            let state = Arc::new(AppState {
+                config: RwLock::new(config.clone()),
+                db,
+                start_time: std::time::Instant::now(),
+                config_path: config_path.clone(),
+                session_secret: session_secret.to_vec(),
+                metrics: metrics::Metrics::new(),
+            });
+            check_for_ads(state, urls).await?;
+            return Ok(());
+        }
+        Command::Serve => {}
+    }
+
     // Load config first to get log filename
     let config = match Config::load(&config_path) {
         Ok(c) => c,
@@ -153,6 +380,19 @@ async fn main() -> Result<()> {
                 log_filename: "fb-rssfeed.log".to_string(),
                 database_name: "fb-rss-feed.db".to_string(),
                 url_filters: std::collections::HashMap::new(),
+                admin_username: String::new(),
+                admin_password_salt: String::new(),
+                admin_password_hash: String::new(),
+                max_scrolls: 0,
+                scroll_delay_min_ms: 0,
+                scroll_delay_max_ms: 0,
+                firefox_preferences: std::collections::HashMap::new(),
+                schedule: None,
+                url_schedules: std::collections::HashMap::new(),
+                max_concurrent_scrapers: 0,
+                politeness_delay_seconds: 0,
+                media_storage: None,
+                http_first: false,
             }
         }
     };
@@ -176,28 +416,35 @@ async fn main() -> Result<()> {
     let server_ip = config.server_ip.clone();
     let server_port = config.server_port;
 
+    let session_secret: [u8; 32] = rand::random();
+
     let state = Arc::new(AppState {
         config: RwLock::new(config.clone()),
         db,
         start_time: std::time::Instant::now(),
         config_path: config_path.clone(),
+        session_secret: session_secret.to_vec(),
+        metrics: crate::metrics::Metrics::new(),
     });
 
     // Start background task
     let bg_state = Arc::clone(&state);
     tokio::spawn(async move {
+        let mut due_at: HashMap<String, DateTime<Utc>> = HashMap::new();
         loop {
-            let interval = {
+            let (urls, sleep_for) = {
                 let c = bg_state.config.read().await;
-                c.refresh_interval_minutes
+                due_urls_and_next_sleep(&c, &mut due_at)
             };
 
-            if let Err(e) = check_for_ads(Arc::clone(&bg_state)).await {
-                error!("Error in background ad check: {}", e);
+            if !urls.is_empty() {
+                if let Err(e) = check_for_ads(Arc::clone(&bg_state), urls).await {
+                    error!("Error in background ad check: {}", e);
+                }
             }
 
-            info!("Sleeping for {} minutes...", interval);
-            sleep(Duration::from_secs(interval * 60)).await;
+            info!("Sleeping for {:?}...", sleep_for);
+            sleep(sleep_for).await;
         }
     });
 
@@ -223,6 +470,7 @@ mod e2e_tests {
     use super::*;
     use crate::config::Config;
     use reqwest::Client;
+    use serde_json::json;
     use std::time::Duration;
     use tempfile::NamedTempFile;
 
@@ -239,6 +487,19 @@ mod e2e_tests {
             log_filename: "test.log".to_string(),
             database_name: db_path,
             url_filters: std::collections::HashMap::new(),
+            admin_username: "admin".to_string(),
+            admin_password_salt: "salt".to_string(),
+            admin_password_hash: crate::auth::hash_password("password", "salt"),
+            max_scrolls: 0,
+            scroll_delay_min_ms: 0,
+            scroll_delay_max_ms: 0,
+            firefox_preferences: std::collections::HashMap::new(),
+            schedule: None,
+            url_schedules: std::collections::HashMap::new(),
+            max_concurrent_scrapers: 0,
+            politeness_delay_seconds: 0,
+            media_storage: None,
+            http_first: false,
         };
 
         let db = Database::new(&config.database_name).unwrap();
@@ -250,6 +511,8 @@ mod e2e_tests {
             db,
             start_time: std::time::Instant::now(),
             config_path,
+            session_secret: b"test-session-secret".to_vec(),
+            metrics: crate::metrics::Metrics::new(),
         });
 
         let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -263,7 +526,7 @@ mod e2e_tests {
         // Give server a moment to start
         tokio::time::sleep(Duration::from_millis(100)).await;
 
-        let client = Client::new();
+        let client = Client::builder().cookie_store(true).build().unwrap();
         let base_url = format!("http://127.0.0.1:{}", port);
 
         // Test health check
@@ -276,6 +539,22 @@ mod e2e_tests {
         let health_json: serde_json::Value = resp.json().await.unwrap();
         assert_eq!(health_json["status"], "up");
 
+        // Config routes require an authenticated session
+        let resp = client
+            .get(format!("{}/api/config", base_url))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 401);
+
+        let resp = client
+            .post(format!("{}/api/login", base_url))
+            .json(&json!({"username": "admin", "password": "password"}))
+            .send()
+            .await
+            .unwrap();
+        assert!(resp.status().is_success());
+
         // Test get config
         let resp = client
             .get(format!("{}/api/config", base_url))
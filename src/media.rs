@@ -0,0 +1,152 @@
+use crate::config::{MediaStorageConfig, MediaStorageKind};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Downloads a listing thumbnail once and re-serves it from a stable URL, so
+/// feed readers don't hot-link Facebook's ephemeral CDN links.
+#[async_trait]
+pub trait MediaStore: Send + Sync {
+    /// Caches `source_url` for `ad_id` if it isn't already cached, and
+    /// returns the stable URL callers should use instead.
+    async fn store(&self, ad_id: &str, source_url: &str) -> Result<String>;
+}
+
+/// Builds the configured `MediaStore`, or `None` when `media_storage` is unset
+/// (callers should fall back to the original CDN URL in that case).
+pub fn build_media_store(config: &Option<MediaStorageConfig>) -> Option<Arc<dyn MediaStore>> {
+    let media_storage = config.as_ref()?;
+    match media_storage.kind {
+        MediaStorageKind::Filesystem => Some(Arc::new(FilesystemStore::new(
+            &media_storage.location,
+            &media_storage.public_base_url,
+        ))),
+        #[cfg(feature = "s3-store")]
+        MediaStorageKind::S3 => Some(Arc::new(S3Store::new(
+            &media_storage.location,
+            &media_storage.public_base_url,
+        ))),
+        #[cfg(not(feature = "s3-store"))]
+        MediaStorageKind::S3 => {
+            tracing::warn!(
+                "media_storage.kind is \"s3\" but this build lacks the s3-store feature; \
+                 falling back to hot-linking the original CDN URL"
+            );
+            None
+        }
+    }
+}
+
+/// Caches images on the local filesystem, served back out from
+/// `public_base_url` (e.g. via a reverse proxy or a static file route).
+pub struct FilesystemStore {
+    base_path: PathBuf,
+    public_base_url: String,
+    client: reqwest::Client,
+}
+
+impl FilesystemStore {
+    pub fn new(base_path: impl Into<PathBuf>, public_base_url: impl Into<String>) -> Self {
+        FilesystemStore {
+            base_path: base_path.into(),
+            public_base_url: public_base_url.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn file_name(ad_id: &str) -> String {
+        format!("{}.jpg", ad_id)
+    }
+}
+
+#[async_trait]
+impl MediaStore for FilesystemStore {
+    async fn store(&self, ad_id: &str, source_url: &str) -> Result<String> {
+        let file_name = Self::file_name(ad_id);
+        let dest = self.base_path.join(&file_name);
+
+        if !dest.exists() {
+            std::fs::create_dir_all(&self.base_path)?;
+            let bytes = self.client.get(source_url).send().await?.bytes().await?;
+            std::fs::write(&dest, &bytes)?;
+        }
+
+        Ok(format!(
+            "{}/{}",
+            self.public_base_url.trim_end_matches('/'),
+            file_name
+        ))
+    }
+}
+
+/// S3-compatible bucket store, gated behind the `s3-store` feature. Its
+/// `store` uploads nothing: it always returns an error, so callers keep the
+/// original CDN URL.
+#[cfg(feature = "s3-store")]
+pub struct S3Store {
+    bucket: String,
+    public_base_url: String,
+}
+
+#[cfg(feature = "s3-store")]
+impl S3Store {
+    pub fn new(bucket: impl Into<String>, public_base_url: impl Into<String>) -> Self {
+        S3Store {
+            bucket: bucket.into(),
+            public_base_url: public_base_url.into(),
+        }
+    }
+}
+
+#[cfg(feature = "s3-store")]
+#[async_trait]
+impl MediaStore for S3Store {
+    async fn store(&self, _ad_id: &str, _source_url: &str) -> Result<String> {
+        // No S3 client is part of this build, so nothing is uploaded to
+        // `self.bucket`. Returning an error instead of a public URL for a
+        // file that was never written makes callers (see
+        // `main.rs::scrape_one_url`) fall back to the original CDN URL.
+        Err(anyhow!(
+            "s3-store is not yet implemented (no AWS client is wired into bucket \"{}\"); \
+             falling back to the source URL instead of serving a broken link",
+            self.bucket
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MediaStorageConfig;
+
+    #[tokio::test]
+    async fn test_filesystem_store_reuses_cached_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FilesystemStore::new(dir.path(), "https://example.com/media");
+
+        let file_name = FilesystemStore::file_name("cached_ad");
+        std::fs::write(dir.path().join(&file_name), b"fake-image-bytes").unwrap();
+
+        let url = store
+            .store("cached_ad", "https://cdn.example.com/ignored.jpg")
+            .await
+            .unwrap();
+        assert_eq!(url, "https://example.com/media/cached_ad.jpg");
+    }
+
+    #[test]
+    fn test_build_media_store_returns_none_when_unconfigured() {
+        assert!(build_media_store(&None).is_none());
+    }
+
+    #[test]
+    fn test_build_media_store_returns_filesystem_store_when_configured() {
+        let config = Some(MediaStorageConfig {
+            kind: MediaStorageKind::Filesystem,
+            location: "/tmp/fbrss-media".to_string(),
+            public_base_url: "https://example.com/media".to_string(),
+        });
+        assert!(build_media_store(&config).is_some());
+    }
+}
@@ -0,0 +1,143 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Process-lifetime scrape counters, exposed at `GET /metrics` in Prometheus
+/// text format. Atomics so the background scrape task can update them
+/// through a shared `Arc<AppState>` without taking the config lock.
+#[derive(Default)]
+pub struct Metrics {
+    scrape_attempts_total: AtomicU64,
+    scraper_init_failures_total: AtomicU64,
+    fetch_failures_total: AtomicU64,
+    ads_extracted_total: AtomicU64,
+    ads_inserted_total: AtomicU64,
+    last_scrape_duration_ms: AtomicU64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn inc_scrape_attempts(&self) {
+        self.scrape_attempts_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn inc_scraper_init_failures(&self) {
+        self.scraper_init_failures_total
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn inc_fetch_failures(&self) {
+        self.fetch_failures_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn add_ads_extracted(&self, count: u64) {
+        self.ads_extracted_total.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn inc_ads_inserted(&self) {
+        self.ads_inserted_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_scrape_duration(&self, duration: Duration) {
+        self.last_scrape_duration_ms
+            .store(duration.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Renders all counters/gauges, plus the given `ad_rows_total` gauge
+    /// (the current row count in `ad_changes`), as Prometheus text format.
+    pub fn render(&self, ad_rows_total: u64) -> String {
+        let mut out = String::new();
+        push_counter(
+            &mut out,
+            "fbrss_scrape_attempts_total",
+            "Total number of per-URL scrape attempts",
+            self.scrape_attempts_total.load(Ordering::Relaxed),
+        );
+        push_counter(
+            &mut out,
+            "fbrss_scraper_init_failures_total",
+            "Total number of scraper initializations that failed after retries",
+            self.scraper_init_failures_total.load(Ordering::Relaxed),
+        );
+        push_counter(
+            &mut out,
+            "fbrss_fetch_failures_total",
+            "Total number of per-URL page fetches that failed after retries",
+            self.fetch_failures_total.load(Ordering::Relaxed),
+        );
+        push_counter(
+            &mut out,
+            "fbrss_ads_extracted_total",
+            "Total number of ad listings extracted from fetched pages",
+            self.ads_extracted_total.load(Ordering::Relaxed),
+        );
+        push_counter(
+            &mut out,
+            "fbrss_ads_inserted_total",
+            "Total number of new ads inserted into the database",
+            self.ads_inserted_total.load(Ordering::Relaxed),
+        );
+        push_gauge(
+            &mut out,
+            "fbrss_ad_rows",
+            "Current number of ad rows tracked in the database",
+            ad_rows_total,
+        );
+        push_gauge(
+            &mut out,
+            "fbrss_last_scrape_duration_ms",
+            "Duration of the last completed scrape cycle in milliseconds",
+            self.last_scrape_duration_ms.load(Ordering::Relaxed),
+        );
+        out
+    }
+}
+
+fn push_counter(out: &mut String, name: &str, help: &str, value: u64) {
+    out.push_str(&format!(
+        "# HELP {name} {help}\n# TYPE {name} counter\n{name} {value}\n"
+    ));
+}
+
+fn push_gauge(out: &mut String, name: &str, help: &str, value: u64) {
+    out.push_str(&format!(
+        "# HELP {name} {help}\n# TYPE {name} gauge\n{name} {value}\n"
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_includes_help_and_type_lines() {
+        let metrics = Metrics::new();
+        metrics.inc_scrape_attempts();
+        metrics.inc_ads_inserted();
+        metrics.record_scrape_duration(Duration::from_millis(42));
+
+        let text = metrics.render(7);
+        assert!(text.contains("# HELP fbrss_scrape_attempts_total"));
+        assert!(text.contains("# TYPE fbrss_scrape_attempts_total counter"));
+        assert!(text.contains("fbrss_scrape_attempts_total 1"));
+        assert!(text.contains("fbrss_ads_inserted_total 1"));
+        assert!(text.contains("fbrss_ad_rows 7"));
+        assert!(text.contains("fbrss_last_scrape_duration_ms 42"));
+    }
+
+    #[test]
+    fn test_counters_accumulate() {
+        let metrics = Metrics::new();
+        metrics.add_ads_extracted(3);
+        metrics.add_ads_extracted(2);
+        metrics.inc_fetch_failures();
+        metrics.inc_scraper_init_failures();
+
+        let text = metrics.render(0);
+        assert!(text.contains("fbrss_ads_extracted_total 5"));
+        assert!(text.contains("fbrss_fetch_failures_total 1"));
+        assert!(text.contains("fbrss_scraper_init_failures_total 1"));
+    }
+}
@@ -1,24 +1,53 @@
 use crate::db::AdEntry;
 use anyhow::Result;
 use chrono::{Local, Utc};
-use rss::{ChannelBuilder, Guid, ItemBuilder};
+use rss::{ChannelBuilder, EnclosureBuilder, Guid, ItemBuilder};
 
 pub fn generate_rss(entries: &[AdEntry], server_ip: &str, server_port: u16) -> Result<String> {
     let mut items = Vec::new();
 
     for entry in entries {
+        let title = match &entry.previous_price {
+            Some(_) => format!("⬇ price drop: {} - {}", entry.title, entry.price),
+            None => format!("{} - {}", entry.title, entry.price),
+        };
+        let mut description = match &entry.previous_price {
+            Some(old_price) => format!(
+                "Price dropped: {} → {} | Title: {}",
+                old_price, entry.price, entry.title
+            ),
+            None => format!("Price: {} | Title: {}", entry.price, entry.title),
+        };
+        if let Some(image_url) = &entry.image_url {
+            description = format!("<img src=\"{}\"> {}", image_url, description);
+        }
+        // Bump pub_date to when the drop was recorded (not "now") so feed
+        // readers treat it as a new item worth re-notifying exactly once,
+        // and repeated polls of an unchanged drop keep a stable pub_date
+        // (otherwise ETag-based conditional GET never matches).
+        let pub_date = entry
+            .previous_price_observed_at
+            .filter(|_| entry.previous_price.is_some())
+            .unwrap_or(entry.last_checked);
+
+        let enclosure = entry.image_url.as_ref().map(|image_url| {
+            EnclosureBuilder::default()
+                .url(image_url.clone())
+                .mime_type("image/jpeg".to_string())
+                .length("0".to_string())
+                .build()
+        });
+
         let item = ItemBuilder::default()
-            .title(Some(format!("{} - {}", entry.title, entry.price)))
+            .title(Some(title))
             .link(Some(entry.url.clone()))
-            .description(Some(format!(
-                "Price: {} | Title: {}",
-                entry.price, entry.title
-            )))
+            .description(Some(description))
             .guid(Some(Guid {
                 value: entry.ad_id.clone(),
                 permalink: false,
             }))
-            .pub_date(Some(entry.last_checked.with_timezone(&Local).to_rfc2822()))
+            .pub_date(Some(pub_date.with_timezone(&Local).to_rfc2822()))
+            .enclosure(enclosure)
             .build();
         items.push(item);
     }
@@ -57,6 +86,9 @@ mod tests {
             url: "https://example.com/1".to_string(),
             first_seen: now,
             last_checked: now,
+            previous_price: None,
+            previous_price_observed_at: None,
+            image_url: None,
         }];
 
         let rss_xml = generate_rss(&entries, "127.0.0.1", 5000).unwrap();
@@ -64,4 +96,58 @@ mod tests {
         assert!(rss_xml.contains("https://example.com/1"));
         assert!(rss_xml.contains("id1"));
     }
+
+    #[test]
+    fn test_generate_rss_marks_price_drops() {
+        let now = Utc::now();
+        let dropped_at = now - chrono::Duration::hours(3);
+        let entries = vec![AdEntry {
+            ad_id: "id2".to_string(),
+            title: "Ad 2".to_string(),
+            price: "$75".to_string(),
+            url: "https://example.com/2".to_string(),
+            first_seen: now,
+            last_checked: now,
+            previous_price: Some("$100".to_string()),
+            previous_price_observed_at: Some(dropped_at),
+            image_url: None,
+        }];
+
+        let rss_xml = generate_rss(&entries, "127.0.0.1", 5000).unwrap();
+        assert!(rss_xml.contains("⬇ price drop: Ad 2 - $75"));
+        assert!(rss_xml.contains("Price dropped: $100 → $75"));
+        // pub_date must come from when the drop was recorded, not "now" —
+        // otherwise every request gets a fresh timestamp and ETag-based
+        // conditional GET can never match.
+        assert!(rss_xml.contains(&format!(
+            "<pubDate>{}</pubDate>",
+            dropped_at.with_timezone(&Local).to_rfc2822()
+        )));
+        assert!(!rss_xml.contains(&format!(
+            "<pubDate>{}</pubDate>",
+            now.with_timezone(&Local).to_rfc2822()
+        )));
+    }
+
+    #[test]
+    fn test_generate_rss_includes_enclosure_and_inline_image() {
+        let now = Utc::now();
+        let entries = vec![AdEntry {
+            ad_id: "id3".to_string(),
+            title: "Ad 3".to_string(),
+            price: "$20".to_string(),
+            url: "https://example.com/3".to_string(),
+            first_seen: now,
+            last_checked: now,
+            previous_price: None,
+            previous_price_observed_at: None,
+            image_url: Some("https://example.com/media/id3.jpg".to_string()),
+        }];
+
+        let rss_xml = generate_rss(&entries, "127.0.0.1", 5000).unwrap();
+        assert!(rss_xml.contains("<enclosure"));
+        assert!(rss_xml.contains("url=\"https://example.com/media/id3.jpg\""));
+        assert!(rss_xml.contains("type=\"image/jpeg\""));
+        assert!(rss_xml.contains("<img src=\"https://example.com/media/id3.jpg\">"));
+    }
 }
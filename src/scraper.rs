@@ -1,10 +1,48 @@
 use anyhow::{anyhow, Result};
 use md5;
 use scraper::{Html, Selector};
+use serde_json::{json, Value};
+use std::collections::HashMap;
 use thirtyfour::prelude::*;
 
+/// Bounds for the infinite-scroll loop in `Scraper::get_page_content`.
+#[derive(Debug, Clone, Copy)]
+pub struct ScrollConfig {
+    /// Stop scrolling once this many scroll iterations have run, even if the
+    /// ad count is still growing.
+    pub max_scrolls: u32,
+    /// Randomized per-iteration delay bounds (milliseconds) so the scroll
+    /// cadence doesn't look robotic.
+    pub delay_min_ms: u64,
+    pub delay_max_ms: u64,
+}
+
+impl Default for ScrollConfig {
+    fn default() -> Self {
+        ScrollConfig {
+            max_scrolls: 20,
+            delay_min_ms: 800,
+            delay_max_ms: 2500,
+        }
+    }
+}
+
+/// Which fetch strategy `Scraper::get_page_content` uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScraperBackend {
+    /// Always drive a live WebDriver session (the original behavior).
+    WebDriverOnly,
+    /// Try a direct HTTP fetch first; fall back to WebDriver if the response
+    /// is missing the ad container or looks like a soft block.
+    HttpFirst,
+}
+
 pub struct Scraper {
     driver: Option<WebDriver>,
+    scroll_config: ScrollConfig,
+    firefox_preferences: HashMap<String, Value>,
+    backend: ScraperBackend,
+    http_client: Option<reqwest::Client>,
 }
 
 const USER_AGENTS: &[&str] = &[
@@ -17,12 +55,102 @@ const USER_AGENTS: &[&str] = &[
     "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/127.0.0.0 Safari/537.36",
 ];
 
+/// Firefox profile preferences applied by default to cut bandwidth (no image
+/// loading) and detection surface (no WebRTC leaks, no `navigator.webdriver`).
+/// `oscpu`/`platform.override` are tied to the chosen user agent so the
+/// reported OS stays consistent with the UA string.
+fn default_firefox_preferences(user_agent: &str) -> HashMap<String, Value> {
+    let mut prefs = HashMap::new();
+    prefs.insert("permissions.default.image".to_string(), json!(2));
+    prefs.insert("media.peerconnection.enabled".to_string(), json!(false));
+    prefs.insert(
+        "intl.accept_languages".to_string(),
+        json!(accept_languages_for_user_agent(user_agent)),
+    );
+    prefs.insert("dom.webdriver.enabled".to_string(), json!(false));
+    prefs.insert(
+        "general.oscpu.override".to_string(),
+        json!(oscpu_for_user_agent(user_agent)),
+    );
+    let platform = if user_agent.contains("Windows") {
+        "Win32"
+    } else if user_agent.contains("Macintosh") {
+        "MacIntel"
+    } else {
+        "Linux x86_64"
+    };
+    prefs.insert("general.platform.override".to_string(), json!(platform));
+    prefs
+}
+
+fn oscpu_for_user_agent(user_agent: &str) -> &'static str {
+    if user_agent.contains("Windows") {
+        "Windows NT 10.0; Win64; x64"
+    } else if user_agent.contains("Macintosh") {
+        "Intel Mac OS X 10.15"
+    } else {
+        "Linux x86_64"
+    }
+}
+
+/// Every entry in `USER_AGENTS` is a US-English browser string, so this
+/// always resolves to `en-US, en` today; it's kept as a lookup keyed on the
+/// user agent (rather than a hardcoded constant) so adding a non-US UA to
+/// the list later is a one-line change here instead of a silent mismatch.
+fn accept_languages_for_user_agent(_user_agent: &str) -> &'static str {
+    "en-US, en"
+}
+
 impl Scraper {
     pub fn new() -> Self {
-        Scraper { driver: None }
+        Scraper {
+            driver: None,
+            scroll_config: ScrollConfig::default(),
+            firefox_preferences: HashMap::new(),
+            backend: ScraperBackend::WebDriverOnly,
+            http_client: None,
+        }
+    }
+
+    /// Builds a scraper that tries a direct `reqwest` fetch before falling
+    /// back to WebDriver. The HTTP client keeps a cookie jar so a primed
+    /// Facebook session is reused across requests.
+    pub fn new_http_first() -> Result<Self> {
+        let http_client = reqwest::Client::builder()
+            .cookie_store(true)
+            .gzip(true)
+            .build()?;
+
+        Ok(Scraper {
+            driver: None,
+            scroll_config: ScrollConfig::default(),
+            firefox_preferences: HashMap::new(),
+            backend: ScraperBackend::HttpFirst,
+            http_client: Some(http_client),
+        })
     }
 
+    pub fn set_scroll_config(&mut self, scroll_config: ScrollConfig) {
+        self.scroll_config = scroll_config;
+    }
+
+    /// User-supplied Firefox profile preferences, merged over the defaults
+    /// `init` applies and taking precedence on key collisions.
+    pub fn set_firefox_preferences(&mut self, preferences: HashMap<String, Value>) {
+        self.firefox_preferences = preferences;
+    }
+
+    /// Starts the WebDriver session needed for `WebDriverOnly` scraping.
+    /// For `HttpFirst`, this is a no-op — the browser stack is only launched
+    /// on demand, in `get_page_content`, if the direct HTTP fetch fails.
     pub async fn init(&mut self) -> Result<()> {
+        match self.backend {
+            ScraperBackend::WebDriverOnly => self.init_webdriver().await,
+            ScraperBackend::HttpFirst => Ok(()),
+        }
+    }
+
+    async fn init_webdriver(&mut self) -> Result<()> {
         let mut caps = DesiredCapabilities::firefox();
         caps.add_arg("--headless")?;
         caps.add_arg("--no-sandbox")?;
@@ -32,6 +160,12 @@ impl Scraper {
         let ua = USER_AGENTS[rand::random_range(0..USER_AGENTS.len())];
         caps.add_arg(&format!("--user-agent={}", ua))?;
 
+        let mut preferences = default_firefox_preferences(ua);
+        preferences.extend(self.firefox_preferences.clone());
+        for (key, value) in preferences {
+            caps.set_preference(&key, value)?;
+        }
+
         let driver = WebDriver::new("http://localhost:4444", caps).await?;
         self.driver = Some(driver);
         Ok(())
@@ -44,7 +178,53 @@ impl Scraper {
         Ok(())
     }
 
-    pub async fn get_page_content(&self, url: &str) -> Result<String> {
+    pub async fn get_page_content(&mut self, url: &str) -> Result<String> {
+        if self.backend == ScraperBackend::HttpFirst {
+            match self.fetch_via_http(url).await {
+                Ok(html) => return Ok(html),
+                Err(e) => {
+                    tracing::warn!("HTTP fetch for {} unusable, falling back to WebDriver: {}", url, e);
+                }
+            }
+            // HttpFirst never started a WebDriver session in `init`; start
+            // one now, lazily, only because the HTTP fetch actually failed.
+            if self.driver.is_none() {
+                self.init_webdriver().await?;
+            }
+        }
+
+        self.fetch_via_webdriver(url).await
+    }
+
+    /// Direct HTTP fetch, rotating the `User-Agent` per request. Returns an
+    /// error (rather than the page) when the response looks like a soft
+    /// block or is missing the ad container, so the caller can fall back.
+    async fn fetch_via_http(&self, url: &str) -> Result<String> {
+        let client = self
+            .http_client
+            .as_ref()
+            .ok_or_else(|| anyhow!("HTTP backend not initialized"))?;
+
+        let ua = USER_AGENTS[rand::random_range(0..USER_AGENTS.len())];
+        let response = client.get(url).header("User-Agent", ua).send().await?;
+
+        let final_url = response.url().to_string().to_lowercase();
+        if final_url.contains("login") || final_url.contains("checkpoint") {
+            return Err(anyhow!(
+                "Potential soft block detected: redirected to {}",
+                final_url
+            ));
+        }
+
+        let body = response.text().await?;
+        if !has_ad_container(&body) {
+            return Err(anyhow!("HTTP response missing ad container"));
+        }
+
+        Ok(body)
+    }
+
+    async fn fetch_via_webdriver(&self, url: &str) -> Result<String> {
         let driver = self
             .driver
             .as_ref()
@@ -66,16 +246,59 @@ impl Scraper {
             .first()
             .await?;
 
+        self.scroll_until_settled(driver).await?;
+
         let source = driver.source().await?;
         Ok(source)
     }
+
+    /// Repeatedly scrolls to the bottom of the page to trigger Marketplace's
+    /// infinite-scroll loading, stopping once the ad count stops growing for
+    /// two consecutive iterations or the configured scroll cap is hit.
+    async fn scroll_until_settled(&self, driver: &WebDriver) -> Result<()> {
+        let ad_selector = By::Css("div.x87ps6o");
+        let mut last_count = driver.query(ad_selector.clone()).all_from_selector().await?.len();
+        let mut stale_iterations = 0;
+
+        for _ in 0..self.scroll_config.max_scrolls {
+            if stale_iterations >= 2 {
+                break;
+            }
+
+            driver
+                .execute("window.scrollTo(0, document.body.scrollHeight);", vec![])
+                .await?;
+
+            let delay_ms =
+                rand::random_range(self.scroll_config.delay_min_ms..=self.scroll_config.delay_max_ms);
+            tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+
+            let count = driver.query(ad_selector.clone()).all_from_selector().await?.len();
+            if count > last_count {
+                stale_iterations = 0;
+            } else {
+                stale_iterations += 1;
+            }
+            last_count = count;
+        }
+
+        Ok(())
+    }
 }
 
-pub fn extract_ads(html_content: &str, currency: &str) -> Vec<(String, String, String, String)> {
+/// Extracts `(ad_id, title, price, url, image_url)` tuples for every listing
+/// on the page whose price matches `currency`. `image_url` is the listing's
+/// thumbnail straight off Facebook's CDN (ephemeral; see `media::MediaStore`
+/// for caching it behind a stable URL).
+pub fn extract_ads(
+    html_content: &str,
+    currency: &str,
+) -> Vec<(String, String, String, String, Option<String>)> {
     let document = Html::parse_document(html_content);
     let ad_link_selector = Selector::parse("a[href^='/marketplace/item/']").unwrap();
     let title_selector = Selector::parse("span[style*='-webkit-line-clamp']").unwrap();
     let price_selector = Selector::parse("span[dir='auto']").unwrap();
+    let image_selector = Selector::parse("img").unwrap();
 
     let mut ads = Vec::new();
     let mut processed_urls = std::collections::HashSet::new();
@@ -101,11 +324,17 @@ pub fn extract_ads(html_content: &str, currency: &str) -> Vec<(String, String, S
             .next()
             .map(|el| el.text().collect::<String>().trim().to_string());
 
+        let image_url = ad_link
+            .select(&image_selector)
+            .next()
+            .and_then(|el| el.value().attr("src"))
+            .map(|src| src.to_string());
+
         if let (Some(t), Some(p)) = (title, price) {
             // Validate price starts with configured currency symbol or is free
             if p.starts_with(currency) || p.to_lowercase().contains("free") {
                 let id_hash = get_ad_hash(&full_url);
-                ads.push((id_hash, t, p, full_url));
+                ads.push((id_hash, t, p, full_url, image_url));
             }
         }
     }
@@ -117,10 +346,82 @@ pub fn get_ad_hash(url: &str) -> String {
     format!("{:x}", md5::compute(url))
 }
 
+/// True when the HTML contains at least one Marketplace ad container.
+/// Used by the HTTP backend to decide whether a direct fetch is usable.
+fn has_ad_container(html: &str) -> bool {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("div.x87ps6o").unwrap();
+    document.select(&selector).next().is_some()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_has_ad_container() {
+        let with_ads = r#"<div role="main"><div class="x87ps6o">ad</div></div>"#;
+        let without_ads = "<div>No ads here</div>";
+        assert!(has_ad_container(with_ads));
+        assert!(!has_ad_container(without_ads));
+    }
+
+    #[test]
+    fn test_new_http_first_uses_http_backend() {
+        let scraper = Scraper::new_http_first().unwrap();
+        assert_eq!(scraper.backend, ScraperBackend::HttpFirst);
+        assert!(scraper.http_client.is_some());
+    }
+
+    #[test]
+    fn test_new_defaults_to_webdriver_only() {
+        let scraper = Scraper::new();
+        assert_eq!(scraper.backend, ScraperBackend::WebDriverOnly);
+        assert!(scraper.http_client.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_init_is_a_noop_for_http_first_backend() {
+        // HttpFirst shouldn't require a running geckodriver/Firefox; `init`
+        // must not try to start a WebDriver session for it.
+        let mut scraper = Scraper::new_http_first().unwrap();
+        scraper.init().await.unwrap();
+        assert!(scraper.driver.is_none());
+    }
+
+    #[test]
+    fn test_oscpu_tied_to_user_agent_platform() {
+        assert_eq!(
+            oscpu_for_user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64)"),
+            "Windows NT 10.0; Win64; x64"
+        );
+        assert_eq!(
+            oscpu_for_user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7)"),
+            "Intel Mac OS X 10.15"
+        );
+        assert_eq!(
+            oscpu_for_user_agent("Mozilla/5.0 (X11; Linux x86_64)"),
+            "Linux x86_64"
+        );
+    }
+
+    #[test]
+    fn test_default_firefox_preferences_disable_images_and_webrtc() {
+        let prefs = default_firefox_preferences("Mozilla/5.0 (Windows NT 10.0; Win64; x64)");
+        assert_eq!(prefs.get("permissions.default.image"), Some(&json!(2)));
+        assert_eq!(
+            prefs.get("media.peerconnection.enabled"),
+            Some(&json!(false))
+        );
+    }
+
+    #[test]
+    fn test_scroll_config_default_bounds() {
+        let cfg = ScrollConfig::default();
+        assert!(cfg.max_scrolls > 0);
+        assert!(cfg.delay_min_ms < cfg.delay_max_ms);
+    }
+
     #[test]
     fn test_extract_ads_single() {
         let html = r#"
@@ -131,10 +432,28 @@ mod tests {
         "#;
         let ads = extract_ads(html, "$");
         assert_eq!(ads.len(), 1);
-        let (_hash, title, price, url) = &ads[0];
+        let (_hash, title, price, url, image_url) = &ads[0];
         assert_eq!(title, "Awesome iPhone 15");
         assert_eq!(price, "$800");
         assert!(url.contains("123456789"));
+        assert_eq!(image_url, &None);
+    }
+
+    #[test]
+    fn test_extract_ads_captures_thumbnail() {
+        let html = r#"
+            <a href="/marketplace/item/123456789/?ref=search">
+                <img src="https://scontent.fbcdn.net/thumb.jpg" />
+                <span style="-webkit-line-clamp: 2;">Awesome iPhone 15</span>
+                <span dir="auto">$800</span>
+            </a>
+        "#;
+        let ads = extract_ads(html, "$");
+        assert_eq!(ads.len(), 1);
+        assert_eq!(
+            ads[0].4.as_deref(),
+            Some("https://scontent.fbcdn.net/thumb.jpg")
+        );
     }
 
     #[test]
@@ -1,12 +1,18 @@
+use crate::auth::{hash_password, issue_session_cookie, signatures_match, AuthSession};
 use crate::config::Config;
 use crate::db::Database;
+use crate::metrics::Metrics;
+use crate::scraper::get_ad_hash;
 use axum::{
-    extract::State,
+    extract::{Request, State},
+    http::HeaderMap,
+    middleware::{self, Next},
     response::{IntoResponse, Response},
-    routing::get,
+    routing::{get, post},
     Json, Router,
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use serde_json::json;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -17,19 +23,80 @@ pub struct AppState {
     pub db: Database,
     pub start_time: std::time::Instant,
     pub config_path: String,
+    /// Server-side HMAC key used to sign session cookies. Generated fresh at
+    /// startup, so restarting the server invalidates any outstanding sessions.
+    pub session_secret: Vec<u8>,
+    /// Scrape counters surfaced at `GET /metrics`, updated by the background
+    /// scrape task.
+    pub metrics: Metrics,
+}
+
+/// CSP applied to the hand-authored `/edit` page and the static assets it pulls in.
+const EDIT_PAGE_CSP: &str =
+    "default-src 'self'; img-src 'self' data:; style-src 'self' 'unsafe-inline'; script-src 'self'";
+
+/// Disables browser features this server never needs, regardless of route.
+const PERMISSIONS_POLICY: &str =
+    "accelerometer=(), camera=(), microphone=(), geolocation=(), payment=()";
+
+/// Tower middleware that stamps every response with baseline security headers and,
+/// depending on the request path, the caching behavior appropriate for that resource.
+async fn app_headers(req: Request, next: Next) -> Response {
+    let path = req.uri().path().to_string();
+    let mut response = next.run(req).await;
+    let headers = response.headers_mut();
+
+    headers.insert(
+        "X-Content-Type-Options",
+        axum::http::HeaderValue::from_static("nosniff"),
+    );
+    headers.insert(
+        "X-Frame-Options",
+        axum::http::HeaderValue::from_static("SAMEORIGIN"),
+    );
+    headers.insert(
+        "Permissions-Policy",
+        axum::http::HeaderValue::from_static(PERMISSIONS_POLICY),
+    );
+
+    if path == "/edit" || path.starts_with("/static") {
+        headers.insert(
+            "Content-Security-Policy",
+            axum::http::HeaderValue::from_static(EDIT_PAGE_CSP),
+        );
+    }
+
+    if path.starts_with("/static") {
+        headers.insert(
+            "Cache-Control",
+            axum::http::HeaderValue::from_static("public, max-age=86400"),
+        );
+    } else if (path == "/rss" || path == "/api/config") && !headers.contains_key("Cache-Control") {
+        // Handlers that need finer-grained caching (e.g. /rss's ETag-based
+        // revalidation) set their own Cache-Control and take precedence.
+        headers.insert(
+            "Cache-Control",
+            axum::http::HeaderValue::from_static("no-store"),
+        );
+    }
+
+    response
 }
 
 pub fn app(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/health", get(health_check))
+        .route("/metrics", get(metrics_handler))
         .route("/rss", get(rss_feed))
         .route("/edit", get(edit_config_page))
         .route("/api/config", get(get_config).post(update_config))
+        .route("/api/login", post(login))
         .nest_service("/static", ServeDir::new("static"))
+        .layer(middleware::from_fn(app_headers))
         .with_state(state)
 }
 
-async fn edit_config_page() -> impl IntoResponse {
+async fn edit_config_page(_auth: AuthSession) -> impl IntoResponse {
     let html = match std::fs::read_to_string("templates/edit_config.html") {
         Ok(h) => h,
         Err(_) => return (axum::http::StatusCode::NOT_FOUND, "Template not found").into_response(),
@@ -37,6 +104,36 @@ async fn edit_config_page() -> impl IntoResponse {
     axum::response::Html(html).into_response()
 }
 
+#[derive(Deserialize)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+async fn login(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<LoginRequest>,
+) -> impl IntoResponse {
+    let config = state.config.read().await;
+    let submitted_hash = hash_password(&req.password, &config.admin_password_salt);
+    if req.username != config.admin_username
+        || !signatures_match(&submitted_hash, &config.admin_password_hash)
+    {
+        return (
+            axum::http::StatusCode::UNAUTHORIZED,
+            Json(json!({"detail": "Invalid username or password"})),
+        )
+            .into_response();
+    }
+
+    let cookie = issue_session_cookie(&state.session_secret, &req.username);
+    (
+        [(axum::http::header::SET_COOKIE, cookie)],
+        Json(json!({"message": "Logged in"})),
+    )
+        .into_response()
+}
+
 async fn health_check(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     let config = state.config.read().await;
     let uptime_secs = state.start_time.elapsed().as_secs();
@@ -49,7 +146,15 @@ async fn health_check(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     Json(status)
 }
 
-async fn rss_feed(State(state): State<Arc<AppState>>) -> Response {
+async fn metrics_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let ad_rows_total = state.db.count_ads().unwrap_or(0);
+    (
+        [(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(ad_rows_total),
+    )
+}
+
+async fn rss_feed(State(state): State<Arc<AppState>>, headers: HeaderMap) -> Response {
     let ads = match state.db.get_recent_ads(7) {
         Ok(a) => a,
         Err(_) => {
@@ -62,25 +167,75 @@ async fn rss_feed(State(state): State<Arc<AppState>>) -> Response {
     };
 
     let config = state.config.read().await;
-    match crate::rss_gen::generate_rss(&ads, &config.server_ip, config.server_port) {
-        Ok(xml) => Response::builder()
-            .header("content-type", "application/rss+xml")
-            .body(axum::body::Body::from(xml))
-            .unwrap(),
-        Err(_) => (
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            "RSS generation error",
-        )
-            .into_response(),
+    let xml = match crate::rss_gen::generate_rss(&ads, &config.server_ip, config.server_port) {
+        Ok(xml) => xml,
+        Err(_) => {
+            return (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                "RSS generation error",
+            )
+                .into_response()
+        }
+    };
+
+    let etag = format!("\"{}\"", get_ad_hash(&xml));
+    let last_modified = ads
+        .iter()
+        .map(|ad| ad.first_seen)
+        .max()
+        .unwrap_or_else(Utc::now);
+    let last_modified_str = last_modified.to_rfc2822();
+    let max_age = config.refresh_interval_minutes * 60;
+
+    if request_not_modified(&headers, &etag, last_modified) {
+        return Response::builder()
+            .status(axum::http::StatusCode::NOT_MODIFIED)
+            .header("etag", &etag)
+            .header("last-modified", &last_modified_str)
+            .header("cache-control", format!("max-age={}", max_age))
+            .body(axum::body::Body::empty())
+            .unwrap();
     }
+
+    Response::builder()
+        .header("content-type", "application/rss+xml")
+        .header("etag", &etag)
+        .header("last-modified", &last_modified_str)
+        .header("cache-control", format!("max-age={}", max_age))
+        .body(axum::body::Body::from(xml))
+        .unwrap()
 }
 
-async fn get_config(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+/// Returns true when the request's conditional headers indicate the client's
+/// cached copy of the feed is still current.
+fn request_not_modified(headers: &HeaderMap, etag: &str, last_modified: DateTime<Utc>) -> bool {
+    if let Some(if_none_match) = headers.get("if-none-match").and_then(|v| v.to_str().ok()) {
+        if if_none_match == etag {
+            return true;
+        }
+    }
+
+    if let Some(if_modified_since) = headers
+        .get("if-modified-since")
+        .and_then(|v| v.to_str().ok())
+    {
+        if let Ok(since) = DateTime::parse_from_rfc2822(if_modified_since) {
+            if last_modified <= since.with_timezone(&Utc) {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+async fn get_config(_auth: AuthSession, State(state): State<Arc<AppState>>) -> impl IntoResponse {
     let config = state.config.read().await;
     Json(config.clone())
 }
 
 async fn update_config(
+    _auth: AuthSession,
     State(state): State<Arc<AppState>>,
     Json(new_config): Json<Config>,
 ) -> impl IntoResponse {
@@ -130,6 +285,19 @@ mod tests {
             log_filename: "test.log".to_string(),
             database_name: ":memory:".to_string(),
             url_filters: std::collections::HashMap::new(),
+            admin_username: "admin".to_string(),
+            admin_password_salt: "salt".to_string(),
+            admin_password_hash: crate::auth::hash_password("password", "salt"),
+            max_scrolls: 0,
+            scroll_delay_min_ms: 0,
+            scroll_delay_max_ms: 0,
+            firefox_preferences: std::collections::HashMap::new(),
+            schedule: None,
+            url_schedules: std::collections::HashMap::new(),
+            max_concurrent_scrapers: 0,
+            politeness_delay_seconds: 0,
+            media_storage: None,
+            http_first: false,
         };
         let db = Database::new(":memory:").unwrap();
         Arc::new(AppState {
@@ -137,9 +305,40 @@ mod tests {
             db,
             start_time: std::time::Instant::now(),
             config_path: "dummy_config.json".to_string(),
+            session_secret: b"test-session-secret".to_vec(),
+            metrics: Metrics::new(),
         })
     }
 
+    async fn login_cookie(app: &Router) -> String {
+        let response = app
+            .clone()
+            .oneshot(
+                axum::http::Request::builder()
+                    .method("POST")
+                    .uri("/api/login")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({"username": "admin", "password": "password"})
+                            .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        response
+            .headers()
+            .get(axum::http::header::SET_COOKIE)
+            .expect("login did not set a session cookie")
+            .to_str()
+            .unwrap()
+            .split(';')
+            .next()
+            .unwrap()
+            .to_string()
+    }
+
     #[tokio::test]
     async fn test_health_check() {
         let state = make_state();
@@ -209,6 +408,7 @@ mod tests {
     async fn test_update_config_valid_and_invalid() {
         let state = make_state();
         let app = app(state.clone());
+        let cookie = login_cookie(&app).await;
 
         let mut config = Config {
             server_ip: "127.0.0.1".to_string(),
@@ -218,6 +418,19 @@ mod tests {
             log_filename: "test.log".to_string(),
             database_name: "test.db".to_string(),
             url_filters: std::collections::HashMap::new(),
+            admin_username: "admin".to_string(),
+            admin_password_salt: "salt".to_string(),
+            admin_password_hash: crate::auth::hash_password("password", "salt"),
+            max_scrolls: 0,
+            scroll_delay_min_ms: 0,
+            scroll_delay_max_ms: 0,
+            firefox_preferences: std::collections::HashMap::new(),
+            schedule: None,
+            url_schedules: std::collections::HashMap::new(),
+            max_concurrent_scrapers: 0,
+            politeness_delay_seconds: 0,
+            media_storage: None,
+            http_first: false,
         };
 
         // Invalid config
@@ -229,6 +442,7 @@ mod tests {
                     .method("POST")
                     .uri("/api/config")
                     .header("content-type", "application/json")
+                    .header(axum::http::header::COOKIE, &cookie)
                     .body(Body::from(serde_json::to_string(&config).unwrap()))
                     .unwrap(),
             )
@@ -244,11 +458,13 @@ mod tests {
         // Valid config
         config.server_port = 8080;
         let response = app
+            .clone()
             .oneshot(
                 axum::http::Request::builder()
                     .method("POST")
                     .uri("/api/config")
                     .header("content-type", "application/json")
+                    .header(axum::http::header::COOKIE, &cookie)
                     .body(Body::from(serde_json::to_string(&config).unwrap()))
                     .unwrap(),
             )
@@ -262,4 +478,193 @@ mod tests {
         assert!(json["message"].is_string());
         assert_eq!(state.config.read().await.server_port, 8080);
     }
+
+    #[tokio::test]
+    async fn test_security_headers_on_every_response() {
+        let state = make_state();
+        let app = app(state);
+        let response = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/health")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let headers = response.headers();
+        assert_eq!(headers.get("X-Content-Type-Options").unwrap(), "nosniff");
+        assert_eq!(headers.get("X-Frame-Options").unwrap(), "SAMEORIGIN");
+        assert!(headers.contains_key("Permissions-Policy"));
+    }
+
+    #[tokio::test]
+    async fn test_no_store_on_config() {
+        let state = make_state();
+        let app = app(state);
+
+        let config_resp = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/api/config")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            config_resp.headers().get("Cache-Control").unwrap(),
+            "no-store"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rss_etag_and_conditional_get() {
+        let state = make_state();
+        let app = app(state);
+
+        let response = app
+            .clone()
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/rss")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key("last-modified"));
+        assert!(
+            response
+                .headers()
+                .get("cache-control")
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .starts_with("max-age=")
+        );
+        let etag = response
+            .headers()
+            .get("etag")
+            .expect("etag header missing")
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let conditional_response = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/rss")
+                    .header("if-none-match", etag)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(conditional_response.status(), StatusCode::NOT_MODIFIED);
+        let body = axum::body::to_bytes(conditional_response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_config_and_edit_require_auth() {
+        let state = make_state();
+        let app = app(state);
+
+        let config_resp = app
+            .clone()
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/api/config")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(config_resp.status(), StatusCode::UNAUTHORIZED);
+
+        let edit_resp = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/edit")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(edit_resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_login_then_access_config() {
+        let state = make_state();
+        let app = app(state);
+        let cookie = login_cookie(&app).await;
+
+        let resp = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/api/config")
+                    .header(axum::http::header::COOKIE, cookie)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_renders_prometheus_text() {
+        let state = make_state();
+        let app = app(state);
+        let response = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "text/plain; version=0.0.4"
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("# HELP fbrss_scrape_attempts_total"));
+        assert!(text.contains("# TYPE fbrss_ad_rows gauge"));
+        assert!(text.contains("fbrss_ad_rows 0"));
+    }
+
+    #[tokio::test]
+    async fn test_login_rejects_wrong_password() {
+        let state = make_state();
+        let app = app(state);
+
+        let resp = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .method("POST")
+                    .uri("/api/login")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({"username": "admin", "password": "wrong"})
+                            .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
 }